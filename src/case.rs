@@ -0,0 +1,95 @@
+//! Resolves `--case`'s smart-case default: search case-insensitively unless
+//! the pattern itself contains an uppercase letter, in which case switch to
+//! case-sensitive. That keeps everyday lowercase queries low-friction while
+//! still letting a deliberately-cased pattern narrow the search.
+
+use clap::ValueEnum;
+
+/// How case is handled when matching `pattern` against names and content.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum CaseMode {
+    /// Case-sensitive unless `pattern` is all lowercase.
+    Smart,
+    Sensitive,
+    Insensitive,
+}
+
+/// Resolves a [`CaseMode`] and a pattern into the effective case-sensitivity
+/// boolean threaded through the rest of the search.
+pub fn resolve(mode: CaseMode, pattern: &str) -> bool {
+    match mode {
+        CaseMode::Sensitive => true,
+        CaseMode::Insensitive => false,
+        CaseMode::Smart => has_uppercase_outside_escapes(pattern),
+    }
+}
+
+/// Scans `pattern` for an uppercase letter that isn't part of an escape
+/// sequence (`\w`, `\.`) or a `\p{...}`/`\x{...}` block, whose contents
+/// (e.g. Unicode property names) shouldn't trigger case sensitivity.
+fn has_uppercase_outside_escapes(pattern: &str) -> bool {
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('p') | Some('x') if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    for escaped in chars.by_ref() {
+                        if escaped == '}' {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        if c.is_uppercase() {
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smart_case_is_insensitive_for_lowercase_pattern() {
+        assert!(!resolve(CaseMode::Smart, "todo"));
+    }
+
+    #[test]
+    fn smart_case_is_sensitive_for_mixed_case_pattern() {
+        assert!(resolve(CaseMode::Smart, "TODO"));
+        assert!(resolve(CaseMode::Smart, "toDo"));
+    }
+
+    #[test]
+    fn sensitive_and_insensitive_ignore_pattern_casing() {
+        assert!(resolve(CaseMode::Sensitive, "todo"));
+        assert!(!resolve(CaseMode::Insensitive, "TODO"));
+    }
+
+    #[test]
+    fn escape_sequences_do_not_trigger_case_sensitivity() {
+        assert!(!has_uppercase_outside_escapes(r"\w+\.rs"));
+    }
+
+    #[test]
+    fn unicode_property_escape_contents_are_skipped() {
+        assert!(!has_uppercase_outside_escapes(r"\p{L}+"));
+        assert!(!has_uppercase_outside_escapes(r"\x{1F600}"));
+    }
+
+    #[test]
+    fn uppercase_outside_an_escape_is_detected() {
+        assert!(has_uppercase_outside_escapes(r"\w+Foo"));
+    }
+
+    #[test]
+    fn unterminated_escape_block_does_not_panic() {
+        assert!(!has_uppercase_outside_escapes(r"\p{L"));
+    }
+}
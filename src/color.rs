@@ -0,0 +1,140 @@
+//! Terminal colorization: resolves `--color auto|always|never`, and maps
+//! `LS_COLORS` (via the `lscolors` crate) to `termcolor` styles for
+//! filename/directory coloring.
+
+use std::io::IsTerminal;
+use std::path::Path;
+
+use clap::ValueEnum;
+use lscolors::{Indicator, LsColors};
+use termcolor::{Color, ColorSpec};
+
+/// When to colorize output. `Auto` colorizes only when stdout is a terminal,
+/// as in fd/ripgrep.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    pub fn to_termcolor_choice(self) -> termcolor::ColorChoice {
+        match self {
+            ColorMode::Auto if std::io::stdout().is_terminal() => termcolor::ColorChoice::Auto,
+            ColorMode::Auto => termcolor::ColorChoice::Never,
+            ColorMode::Always => termcolor::ColorChoice::Always,
+            ColorMode::Never => termcolor::ColorChoice::Never,
+        }
+    }
+}
+
+/// Resolves filename/directory colors from `LS_COLORS`, falling back to
+/// `termcolor`'s defaults (no styling) when it isn't set.
+pub struct PathColorizer {
+    ls_colors: LsColors,
+}
+
+impl PathColorizer {
+    pub fn from_env() -> Self {
+        PathColorizer { ls_colors: LsColors::from_env().unwrap_or_default() }
+    }
+
+    /// The `ColorSpec` to use when printing `path`.
+    pub fn spec_for(&self, path: &Path, is_dir: bool) -> ColorSpec {
+        let style = if is_dir {
+            self.ls_colors.style_for_indicator(Indicator::Directory)
+        } else {
+            self.ls_colors.style_for_path(path)
+        };
+        style.map(style_to_spec).unwrap_or_default()
+    }
+}
+
+fn style_to_spec(style: &lscolors::Style) -> ColorSpec {
+    let mut spec = ColorSpec::new();
+    if let Some(fg) = &style.foreground {
+        let (color, intense) = lscolor_to_termcolor(fg);
+        spec.set_fg(Some(color));
+        if intense {
+            spec.set_intense(true);
+        }
+    }
+    if let Some(bg) = &style.background {
+        let (color, intense) = lscolor_to_termcolor(bg);
+        spec.set_bg(Some(color));
+        if intense {
+            spec.set_intense(true);
+        }
+    }
+    spec.set_bold(style.font_style.bold);
+    spec.set_underline(style.font_style.underline);
+    spec
+}
+
+fn lscolor_to_termcolor(color: &lscolors::Color) -> (Color, bool) {
+    use lscolors::Color as Lsc;
+    match color {
+        Lsc::Black => (Color::Black, false),
+        Lsc::Red => (Color::Red, false),
+        Lsc::Green => (Color::Green, false),
+        Lsc::Yellow => (Color::Yellow, false),
+        Lsc::Blue => (Color::Blue, false),
+        Lsc::Magenta => (Color::Magenta, false),
+        Lsc::Cyan => (Color::Cyan, false),
+        Lsc::White => (Color::White, false),
+        Lsc::BrightBlack => (Color::Black, true),
+        Lsc::BrightRed => (Color::Red, true),
+        Lsc::BrightGreen => (Color::Green, true),
+        Lsc::BrightYellow => (Color::Yellow, true),
+        Lsc::BrightBlue => (Color::Blue, true),
+        Lsc::BrightMagenta => (Color::Magenta, true),
+        Lsc::BrightCyan => (Color::Cyan, true),
+        Lsc::BrightWhite => (Color::White, true),
+        Lsc::Fixed(n) => (Color::Ansi256(*n), false),
+        Lsc::RGB(r, g, b) => (Color::Rgb(*r, *g, *b), false),
+    }
+}
+
+/// The style used to highlight a matched span within a content line.
+pub fn match_highlight_spec() -> ColorSpec {
+    let mut spec = ColorSpec::new();
+    spec.set_fg(Some(Color::Red));
+    spec.set_bold(true);
+    spec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_highlight_is_bold_red() {
+        let spec = match_highlight_spec();
+        assert_eq!(spec.fg(), Some(&Color::Red));
+        assert!(spec.bold());
+    }
+
+    #[test]
+    fn lscolor_to_termcolor_maps_bright_variants_as_intense() {
+        assert_eq!(lscolor_to_termcolor(&lscolors::Color::Blue), (Color::Blue, false));
+        assert_eq!(lscolor_to_termcolor(&lscolors::Color::BrightBlue), (Color::Blue, true));
+        assert_eq!(lscolor_to_termcolor(&lscolors::Color::Fixed(200)), (Color::Ansi256(200), false));
+        assert_eq!(lscolor_to_termcolor(&lscolors::Color::RGB(1, 2, 3)), (Color::Rgb(1, 2, 3), false));
+    }
+
+    #[test]
+    fn path_colorizer_resolves_directory_style_from_ls_colors() {
+        let colorizer = PathColorizer { ls_colors: lscolors::LsColors::from_string("di=01;34") };
+        let spec = colorizer.spec_for(Path::new("anything"), true);
+        assert_eq!(spec.fg(), Some(&Color::Blue));
+        assert!(spec.bold());
+    }
+
+    #[test]
+    fn path_colorizer_defaults_to_no_styling_without_a_match() {
+        let colorizer = PathColorizer { ls_colors: lscolors::LsColors::from_string("") };
+        let spec = colorizer.spec_for(Path::new("plain.txt"), false);
+        assert_eq!(spec, ColorSpec::new());
+    }
+}
@@ -0,0 +1,166 @@
+//! Resolves `--encoding` to a `grep_searcher::Encoding` (sniffing a BOM for
+//! the `auto` default) and opens `-z`/`--search-zip` sources, transparently
+//! decompressing gzip/bzip2/xz/zstd before a searcher ever sees them.
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use grep_searcher::Encoding;
+
+/// Resolves the `--encoding` flag into a `grep_searcher` [`Encoding`].
+/// `"auto"` sniffs a BOM from the first bytes of `path`; any other label is
+/// passed straight through to [`Encoding::new`]. Returns `None` for `"auto"`
+/// with no recognized BOM, letting the searcher fall back to its default
+/// (UTF-8 lossy) decoding.
+///
+/// When `search_zip` is set, `path` names a compressed file whose magic
+/// bytes aren't a BOM, so sniffing it directly would never match; skip our
+/// own sniffing and let the searcher auto-detect from the decompressed
+/// stream it's actually given instead.
+pub fn resolve_encoding(label: &str, path: &Path, search_zip: bool) -> Result<Option<Encoding>> {
+    if !label.eq_ignore_ascii_case("auto") {
+        return Encoding::new(label).map(Some).with_context(|| format!("Unknown --encoding '{}'", label));
+    }
+    if search_zip {
+        return Ok(None);
+    }
+
+    match sniff_bom(path)? {
+        Some(bom_label) => Ok(Some(
+            Encoding::new(bom_label).with_context(|| format!("Unsupported BOM encoding '{}'", bom_label))?,
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Inspects the first bytes of `path` for a UTF-8/UTF-16LE/UTF-16BE BOM.
+fn sniff_bom(path: &Path) -> Result<Option<&'static str>> {
+    let mut file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut buf = [0u8; 3];
+    let n = file.read(&mut buf).unwrap_or(0);
+    Ok(match &buf[..n] {
+        [0xEF, 0xBB, 0xBF, ..] => Some("UTF-8"),
+        [0xFF, 0xFE, ..] => Some("UTF-16LE"),
+        [0xFE, 0xFF, ..] => Some("UTF-16BE"),
+        _ => None,
+    })
+}
+
+/// Opens `path` for content search, transparently decompressing `.gz`,
+/// `.bz2`, `.xz`, and `.zst` files when `search_zip` is set.
+pub fn open_reader(path: &Path, search_zip: bool) -> Result<Box<dyn Read>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    if !search_zip {
+        return Ok(Box::new(file));
+    }
+
+    let reader: Box<dyn Read> = match path.extension().and_then(|e| e.to_str()) {
+        Some("gz") => Box::new(flate2::read::GzDecoder::new(file)),
+        Some("bz2") => Box::new(bzip2::read::BzDecoder::new(file)),
+        Some("xz") => Box::new(xz2::read::XzDecoder::new(file)),
+        Some("zst") => Box::new(
+            zstd::stream::read::Decoder::new(file)
+                .with_context(|| format!("Failed to open zstd stream for {}", path.display()))?,
+        ),
+        _ => Box::new(file),
+    };
+    Ok(Box::new(BufReader::new(reader)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write as _;
+
+    /// Writes `contents` to a uniquely-named file under the OS temp dir and
+    /// returns its path; the caller is responsible for removing it.
+    fn write_temp_file(name: &str, contents: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("finder-encoding-test-{}-{}", std::process::id(), name));
+        let mut f = File::create(&path).unwrap();
+        f.write_all(contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn sniffs_known_boms() {
+        let path = write_temp_file("utf8-bom", &[0xEF, 0xBB, 0xBF, b'h', b'i']);
+        assert_eq!(sniff_bom(&path).unwrap(), Some("UTF-8"));
+        std::fs::remove_file(&path).unwrap();
+
+        let path = write_temp_file("utf16le-bom", &[0xFF, 0xFE, b'h', 0x00]);
+        assert_eq!(sniff_bom(&path).unwrap(), Some("UTF-16LE"));
+        std::fs::remove_file(&path).unwrap();
+
+        let path = write_temp_file("utf16be-bom", &[0xFE, 0xFF, 0x00, b'h']);
+        assert_eq!(sniff_bom(&path).unwrap(), Some("UTF-16BE"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn no_bom_sniffs_to_none() {
+        let path = write_temp_file("plain", b"plain text, no bom");
+        assert_eq!(sniff_bom(&path).unwrap(), None);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn resolve_encoding_auto_falls_back_to_none_without_a_bom() {
+        let path = write_temp_file("resolve-plain", b"no bom here");
+        assert!(resolve_encoding("auto", &path, false).unwrap().is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn resolve_encoding_rejects_unknown_label() {
+        let path = write_temp_file("resolve-bad-label", b"irrelevant");
+        assert!(resolve_encoding("not-a-real-encoding", &path, false).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn resolve_encoding_skips_sniffing_zip_sources() {
+        // Compressed magic bytes aren't a text BOM, so auto-detection must
+        // defer to the searcher instead of misreading them.
+        let path = write_temp_file("resolve-zip", &[0x1f, 0x8b, 0x08, 0x00]);
+        assert!(resolve_encoding("auto", &path, true).unwrap().is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn open_reader_passes_plain_files_through_untouched() {
+        let path = write_temp_file("plain-reader", b"hello, world");
+        let mut reader = open_reader(&path, false).unwrap();
+        let mut out = String::new();
+        reader.read_to_string(&mut out).unwrap();
+        assert_eq!(out, "hello, world");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn open_reader_decompresses_gzip_when_search_zip_is_set() {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"hello from gzip").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let path = write_temp_file("archive.gz", &compressed);
+        let mut reader = open_reader(&path, true).unwrap();
+        let mut out = String::new();
+        reader.read_to_string(&mut out).unwrap();
+        assert_eq!(out, "hello from gzip");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn open_reader_decompresses_zstd_when_search_zip_is_set() {
+        let compressed = zstd::stream::encode_all(&b"hello from zstd"[..], 0).unwrap();
+
+        let path = write_temp_file("archive.zst", &compressed);
+        let mut reader = open_reader(&path, true).unwrap();
+        let mut out = String::new();
+        reader.read_to_string(&mut out).unwrap();
+        assert_eq!(out, "hello from zstd");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
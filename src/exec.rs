@@ -0,0 +1,269 @@
+//! Builds and runs the command template behind `-x`/`--exec` and
+//! `-X`/`--exec-batch`: expand fd-style `{}` placeholders against one match
+//! (or all of them, batched) and spawn the result, tracking the worst exit
+//! code across however many children ran.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+
+/// A parsed `-x`/`-X` command template, e.g. `["wc", "-l", "{}"]`.
+pub struct ExecTemplate {
+    tokens: Vec<String>,
+}
+
+impl ExecTemplate {
+    /// Builds a template from the raw tokens following `-x`/`-X`. If none of
+    /// the tokens contain a placeholder, `{}` is appended so the command
+    /// still receives the matched path.
+    pub fn new(mut tokens: Vec<String>) -> Self {
+        let has_placeholder = tokens.iter().any(|t| contains_placeholder(t));
+        if !has_placeholder {
+            tokens.push("{}".to_string());
+        }
+        ExecTemplate { tokens }
+    }
+
+    /// Builds a template for `-X`/`--exec-batch`, where all matched paths are
+    /// spliced into a single `{}` token. The per-match placeholders `{/}`,
+    /// `{//}`, `{.}`, `{/.}` don't have a sensible batch meaning (there's no
+    /// one path to expand them against), so reject a template that uses them
+    /// instead of silently passing the literal braces through.
+    pub fn new_batch(mut tokens: Vec<String>) -> Result<Self> {
+        for token in &tokens {
+            for placeholder in ["{/.}", "{//}", "{/}", "{.}"] {
+                if token.contains(placeholder) {
+                    bail!("'{}' is not supported in a --exec-batch/-X template (only '{{}}' is)", placeholder);
+                }
+            }
+        }
+        if !tokens.iter().any(|t| t.contains("{}")) {
+            tokens.push("{}".to_string());
+        }
+        Ok(ExecTemplate { tokens })
+    }
+
+    /// Expands the template against a single match, for `-x`.
+    fn build_command(&self, path: &Path) -> Option<Command> {
+        let expanded: Vec<String> = self.tokens.iter().map(|t| expand_placeholders(t, path)).collect();
+        command_from(expanded)
+    }
+
+    /// Expands the template against every match at once, for `-X`. The
+    /// first `{}` token is spliced out into one argument per path; if no
+    /// `{}` token is present, all paths are appended at the end.
+    fn build_batch_command(&self, paths: &[PathBuf]) -> Option<Command> {
+        let mut expanded = Vec::new();
+        let mut placed = false;
+        for token in &self.tokens {
+            if token == "{}" {
+                expanded.extend(paths.iter().map(|p| p.to_string_lossy().to_string()));
+                placed = true;
+            } else {
+                expanded.push(token.clone());
+            }
+        }
+        if !placed {
+            expanded.extend(paths.iter().map(|p| p.to_string_lossy().to_string()));
+        }
+        command_from(expanded)
+    }
+}
+
+fn command_from(mut expanded: Vec<String>) -> Option<Command> {
+    if expanded.is_empty() {
+        return None;
+    }
+    let args = expanded.split_off(1);
+    let mut cmd = Command::new(&expanded[0]);
+    cmd.args(args);
+    Some(cmd)
+}
+
+fn contains_placeholder(token: &str) -> bool {
+    ["{/.}", "{//}", "{/}", "{.}", "{}"].iter().any(|p| token.contains(p))
+}
+
+/// Expands fd-style placeholders in `template` for a single `path`:
+/// `{}` full path, `{/}` basename, `{//}` parent dir, `{.}` path without
+/// extension, `{/.}` basename without extension.
+fn expand_placeholders(template: &str, path: &Path) -> String {
+    let full = path.to_string_lossy();
+    let basename = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| full.to_string());
+    let parent = path.parent().map(|p| p.to_string_lossy().into_owned()).unwrap_or_default();
+    let without_ext = strip_extension(path).to_string_lossy().into_owned();
+    let basename_without_ext = strip_extension(Path::new(&basename)).to_string_lossy().into_owned();
+
+    // Substitute all five placeholders in a single left-to-right scan rather
+    // than chaining `.replace()` calls: a replacement value (e.g. `parent`)
+    // comes straight from the filesystem path, and if it happened to contain
+    // a literal placeholder substring, a later chained `.replace()` would
+    // scan back over it and mangle it a second time.
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    'scan: while !rest.is_empty() {
+        for (placeholder, value) in [
+            ("{/.}", basename_without_ext.as_str()),
+            ("{//}", parent.as_str()),
+            ("{/}", basename.as_str()),
+            ("{.}", without_ext.as_str()),
+            ("{}", full.as_ref()),
+        ] {
+            if let Some(tail) = rest.strip_prefix(placeholder) {
+                out.push_str(value);
+                rest = tail;
+                continue 'scan;
+            }
+        }
+        let mut chars = rest.chars();
+        out.push(chars.next().unwrap());
+        rest = chars.as_str();
+    }
+    out
+}
+
+fn strip_extension(path: &Path) -> PathBuf {
+    match path.file_stem() {
+        Some(stem) => match path.parent() {
+            Some(parent) if parent.as_os_str().is_empty() => PathBuf::from(stem),
+            Some(parent) => parent.join(stem),
+            None => PathBuf::from(stem),
+        },
+        None => path.to_path_buf(),
+    }
+}
+
+/// Runs `template` once per path in `paths`, bounded by `threads` concurrent
+/// children, and returns the worst (highest) exit code seen.
+pub fn run_per_match(template: &ExecTemplate, paths: &[PathBuf], threads: usize) -> i32 {
+    let worst = Arc::new(AtomicI32::new(0));
+    let threads = threads.max(1).min(paths.len().max(1));
+    let chunk_size = paths.len().div_ceil(threads).max(1);
+
+    std::thread::scope(|scope| {
+        for chunk in paths.chunks(chunk_size) {
+            let worst = Arc::clone(&worst);
+            scope.spawn(move || {
+                for path in chunk {
+                    run_one(template, path, &worst);
+                }
+            });
+        }
+    });
+
+    worst.load(Ordering::SeqCst)
+}
+
+fn run_one(template: &ExecTemplate, path: &Path, worst: &AtomicI32) {
+    let Some(mut cmd) = template.build_command(path) else {
+        return;
+    };
+    match cmd.status() {
+        Ok(status) => {
+            worst.fetch_max(status.code().unwrap_or(1), Ordering::SeqCst);
+        }
+        Err(e) => {
+            log::warn!("Failed to execute command for {}: {}", path.display(), e);
+            worst.fetch_max(1, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Runs `template` once over all of `paths` batched together, for `-X`.
+pub fn run_batch(template: &ExecTemplate, paths: &[PathBuf]) -> i32 {
+    let Some(mut cmd) = template.build_batch_command(paths) else {
+        return 0;
+    };
+    match cmd.status() {
+        Ok(status) => status.code().unwrap_or(1),
+        Err(e) => {
+            log::warn!("Failed to execute batch command: {}", e);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_all_placeholders() {
+        let path = Path::new("src/sub/file.txt");
+        assert_eq!(expand_placeholders("{}", path), "src/sub/file.txt");
+        assert_eq!(expand_placeholders("{/}", path), "file.txt");
+        assert_eq!(expand_placeholders("{//}", path), "src/sub");
+        assert_eq!(expand_placeholders("{.}", path), "src/sub/file");
+        assert_eq!(expand_placeholders("{/.}", path), "file");
+    }
+
+    #[test]
+    fn expands_placeholder_with_no_parent_directory() {
+        let path = Path::new("file.txt");
+        assert_eq!(expand_placeholders("{//}", path), "");
+        assert_eq!(expand_placeholders("{/.}", path), "file");
+    }
+
+    #[test]
+    fn replacement_values_are_not_rescanned_for_placeholders() {
+        // A parent directory that itself looks like a placeholder must be
+        // substituted verbatim, not scanned again by a later replacement.
+        let path = Path::new("{.}/file.txt");
+        assert_eq!(expand_placeholders("{//} {.}", path), "{.} {.}/file");
+    }
+
+    #[test]
+    fn template_without_a_placeholder_gets_one_appended() {
+        let template = ExecTemplate::new(vec!["echo".to_string()]);
+        assert_eq!(template.tokens, vec!["echo".to_string(), "{}".to_string()]);
+    }
+
+    #[test]
+    fn template_with_a_placeholder_is_left_alone() {
+        let template = ExecTemplate::new(vec!["wc".to_string(), "-l".to_string(), "{}".to_string()]);
+        assert_eq!(template.tokens, vec!["wc".to_string(), "-l".to_string(), "{}".to_string()]);
+    }
+
+    #[test]
+    fn batch_command_splices_one_argument_per_path() {
+        let template = ExecTemplate::new(vec!["echo".to_string(), "{}".to_string()]);
+        let paths = vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")];
+        let cmd = template.build_batch_command(&paths).expect("command");
+        let args: Vec<String> = cmd.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        assert_eq!(args, vec!["a.txt".to_string(), "b.txt".to_string()]);
+    }
+
+    #[test]
+    fn batch_command_appends_all_paths_when_no_placeholder() {
+        let template = ExecTemplate { tokens: vec!["echo".to_string()] };
+        let paths = vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")];
+        let cmd = template.build_batch_command(&paths).expect("command");
+        let args: Vec<String> = cmd.get_args().map(|a| a.to_string_lossy().into_owned()).collect();
+        assert_eq!(args, vec!["a.txt".to_string(), "b.txt".to_string()]);
+    }
+
+    #[test]
+    fn empty_template_yields_no_command() {
+        assert!(command_from(Vec::new()).is_none());
+    }
+
+    #[test]
+    fn batch_template_rejects_per_match_placeholders() {
+        assert!(ExecTemplate::new_batch(vec!["echo".to_string(), "{/}".to_string()]).is_err());
+        assert!(ExecTemplate::new_batch(vec!["echo".to_string(), "{//}".to_string()]).is_err());
+        assert!(ExecTemplate::new_batch(vec!["echo".to_string(), "{.}".to_string()]).is_err());
+        assert!(ExecTemplate::new_batch(vec!["echo".to_string(), "{/.}".to_string()]).is_err());
+    }
+
+    #[test]
+    fn batch_template_accepts_plain_braces() {
+        let template = ExecTemplate::new_batch(vec!["echo".to_string(), "{}".to_string()]).unwrap();
+        assert_eq!(template.tokens, vec!["echo".to_string(), "{}".to_string()]);
+    }
+}
@@ -0,0 +1,98 @@
+//! Compiles `--type`/`--type-not`/`--type-add` into an `ignore::types::Types`
+//! matcher. `TypesBuilder` already knows the common per-language globs
+//! (`rust`, `py`, `md`, ...), so there's nothing to maintain here beyond
+//! translating the three flags into builder calls.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use ignore::types::{Types, TypesBuilder};
+
+/// Builds the compiled [`Types`] matcher for the configured `--type`,
+/// `--type-not`, and `--type-add` flags. Returns `None` when no type
+/// filtering was requested, so callers can skip the check entirely.
+pub fn build_types(whitelist: &[String], blacklist: &[String], additions: &[String]) -> Result<Option<Types>> {
+    if whitelist.is_empty() && blacklist.is_empty() && additions.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = new_builder(additions)?;
+    for name in whitelist {
+        builder.select(name);
+    }
+    for name in blacklist {
+        builder.negate(name);
+    }
+
+    Ok(Some(builder.build().context("Failed to build --type matcher")?))
+}
+
+/// Prints the full type table (`--type-list`), one `name: globs` line per entry.
+pub fn print_type_list(additions: &[String]) -> Result<()> {
+    let types = new_builder(additions)?.build().context("Failed to build --type matcher")?;
+    for def in types.definitions() {
+        println!("{}: {}", def.name(), def.globs().join(", "));
+    }
+    Ok(())
+}
+
+fn new_builder(additions: &[String]) -> Result<TypesBuilder> {
+    let mut builder = TypesBuilder::new();
+    builder.add_defaults();
+    for addition in additions {
+        let (name, glob) = addition
+            .split_once(':')
+            .with_context(|| format!("Invalid --type-add value '{}', expected 'name:glob'", addition))?;
+        builder
+            .add(name, glob)
+            .with_context(|| format!("Invalid --type-add glob for '{}': {}", name, glob))?;
+    }
+    Ok(builder)
+}
+
+/// Returns `true` if `path` (a file, not a directory) should be kept given
+/// the compiled type matcher. Directories are never filtered here: type
+/// globs describe file extensions, and pruning a directory by its own name
+/// would also hide files inside it that could still match.
+pub fn passes_type_filter(types: &Option<Types>, path: &Path) -> bool {
+    match types {
+        Some(types) => !types.matched(path, false).is_ignore(),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_flags_means_no_filter() {
+        assert!(build_types(&[], &[], &[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn whitelist_keeps_only_matching_types() {
+        let types = build_types(&["rust".to_string()], &[], &[]).unwrap();
+        assert!(passes_type_filter(&types, Path::new("main.rs")));
+        assert!(!passes_type_filter(&types, Path::new("main.py")));
+    }
+
+    #[test]
+    fn blacklist_drops_matching_types() {
+        let types = build_types(&[], &["md".to_string()], &[]).unwrap();
+        assert!(!passes_type_filter(&types, Path::new("README.md")));
+        assert!(passes_type_filter(&types, Path::new("main.rs")));
+    }
+
+    #[test]
+    fn type_add_extends_the_table() {
+        let types = build_types(&["foo".to_string()], &[], &["foo:*.foo".to_string()]).unwrap();
+        assert!(passes_type_filter(&types, Path::new("thing.foo")));
+        assert!(!passes_type_filter(&types, Path::new("thing.bar")));
+    }
+
+    #[test]
+    fn rejects_malformed_type_add() {
+        assert!(build_types(&[], &[], &["no-colon-here".to_string()]).is_err());
+    }
+}
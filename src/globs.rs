@@ -0,0 +1,68 @@
+//! Turns the repeated `-g`/`--glob` values into an `ignore::overrides::Override`
+//! for the walker. `OverrideBuilder` handles the matching itself, including
+//! `!`-prefixed negation and pruning directories whose contents are entirely
+//! excluded, so this is just a thin adapter from `Config` to the builder.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use ignore::overrides::Override;
+use ignore::overrides::OverrideBuilder;
+
+/// Builds the compiled [`Override`] matcher for the configured `--glob`
+/// values. Returns `None` when no globs were requested, so callers can skip
+/// applying overrides to the walker entirely.
+pub fn build_overrides(root: &Path, globs: &[String]) -> Result<Option<Override>> {
+    if globs.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = OverrideBuilder::new(root);
+    for glob in globs {
+        builder
+            .add(glob)
+            .with_context(|| format!("Invalid --glob pattern '{}'", glob))?;
+    }
+    Ok(Some(builder.build().context("Failed to build --glob overrides")?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn no_globs_means_no_override() {
+        assert!(build_overrides(Path::new("."), &[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn whitelist_keeps_only_matching_paths() {
+        let overrides = build_overrides(Path::new("."), &["*.rs".to_string()])
+            .unwrap()
+            .unwrap();
+        assert!(overrides.matched("main.rs", false).is_whitelist());
+        assert!(overrides.matched("main.py", false).is_ignore());
+    }
+
+    #[test]
+    fn negation_excludes_matching_paths() {
+        let overrides = build_overrides(Path::new("."), &["!*.log".to_string()])
+            .unwrap()
+            .unwrap();
+        assert!(overrides.matched("debug.log", false).is_ignore());
+        assert!(!overrides.matched("main.rs", false).is_ignore());
+    }
+
+    #[test]
+    fn directory_with_only_excluded_files_is_pruned() {
+        let overrides = build_overrides(Path::new("."), &["*.rs".to_string()])
+            .unwrap()
+            .unwrap();
+        // A directory itself never matches a file glob, so it isn't
+        // whitelisted directly; the walker prunes it once none of its
+        // descendants can match either.
+        assert!(!overrides.matched("target", true).is_whitelist());
+        assert!(!overrides.matched("target/debug.log", false).is_whitelist());
+    }
+}
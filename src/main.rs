@@ -13,6 +13,7 @@ use std::time::Instant;
 
 use anyhow::{Context, Result};
 use clap::{Parser, ValueEnum};
+use grep_matcher::Matcher;
 use grep_regex::RegexMatcher;
 use grep_searcher::sinks::UTF8;
 use grep_searcher::{BinaryDetection, SearcherBuilder};
@@ -22,6 +23,18 @@ use log::{info, warn, error, debug, trace, LevelFilter};
 use regex::Regex;
 use caseless::Caseless;
 
+mod case;
+mod color;
+mod encoding;
+mod exec;
+mod filetypes;
+mod filters;
+mod globs;
+mod printer;
+use case::CaseMode;
+use color::ColorMode;
+use printer::{Match, MatchType, OutputFormat, Printer};
+
 /// CLI Enum for specifying log levels
 #[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
 enum LogLevelCli {
@@ -47,7 +60,7 @@ enum SearchMode {
 #[derive(Parser, Debug)]
 #[command(author, version, about = "A fast file finder tool", long_about = None)]
 struct Config {
-    #[arg(required = true)]
+    #[arg(required_unless_present = "type_list", default_value = "")]
     pattern: String,
     #[arg(default_value = ".")]
     path: PathBuf,
@@ -55,19 +68,94 @@ struct Config {
     mode: SearchMode,
     #[arg(short, long)]
     regex: bool,
-    #[arg(short, long)]
+    /// How to handle case when matching `pattern`. `smart` (the default) is
+    /// case-sensitive only if `pattern` contains an uppercase letter.
+    #[arg(long, value_enum, default_value_t = CaseMode::Smart)]
+    case: CaseMode,
+    #[clap(skip)]
     case_sensitive: bool,
     #[arg(short, long, default_value_t = true)]
     ignore_binary: bool,
     #[arg(short, long)]
     follow_links: bool,
-    #[arg(short, long)]
+    #[arg(long)]
     max_depth: Option<usize>,
     #[arg(short, long, default_value_t = true)]
     progress: bool,
     #[clap(skip)]
     pattern_lowercase: Option<String>,
 
+    /// Output format: human-readable text, or JSON Lines for editors/scripts.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// Only search files of this type (repeatable), e.g. `--type rust`.
+    #[arg(long = "type", value_name = "TYPE")]
+    type_: Vec<String>,
+
+    /// Exclude files of this type (repeatable), e.g. `--type-not md`.
+    #[arg(long = "type-not", value_name = "TYPE")]
+    type_not: Vec<String>,
+
+    /// Add a custom type definition, e.g. `--type-add 'foo:*.foo'` (repeatable).
+    #[arg(long = "type-add", value_name = "NAME:GLOB")]
+    type_add: Vec<String>,
+
+    /// Print the full table of supported file types and exit.
+    #[arg(long = "type-list")]
+    type_list: bool,
+
+    /// Whitelist/blacklist paths by glob, independent of `pattern` (repeatable).
+    /// Prefix with `!` to exclude, e.g. `-g '*.log' -g '!vendor/**'`.
+    #[arg(short = 'g', long = "glob", value_name = "GLOB")]
+    glob: Vec<String>,
+
+    /// Run a command for each match, expanding `{}`/`{/}`/`{//}`/`{.}`/`{/.}`
+    /// placeholders, e.g. `finder TODO -x wc -l {}`. Without a terminating
+    /// `;`, the command swallows every argument to its right; terminate it
+    /// with `;` to follow with other finder flags, e.g. `-x wc -l {} \;`.
+    #[arg(short = 'x', long = "exec", num_args = 1.., allow_hyphen_values = true, value_name = "CMD", value_terminator = ";", conflicts_with = "exec_batch")]
+    exec: Option<Vec<String>>,
+
+    /// Like `--exec`, but runs the command once with all matches batched
+    /// together. Same `;`-termination rule as `--exec` applies.
+    #[arg(short = 'X', long = "exec-batch", num_args = 1.., allow_hyphen_values = true, value_name = "CMD", value_terminator = ";", conflicts_with = "exec")]
+    exec_batch: Option<Vec<String>>,
+
+    /// Bound on concurrent children spawned by `-x`. Defaults to available parallelism.
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Filter by file size (repeatable, AND'd), e.g. `--size +10M --size -1G`.
+    #[arg(long = "size", value_name = "SIZE")]
+    size: Vec<String>,
+
+    /// Only match files modified within this duration-ago or since this
+    /// timestamp, e.g. `2weeks`, `1h30m`, `2024-01-01`.
+    #[arg(long = "changed-within", value_name = "WHEN")]
+    changed_within: Option<String>,
+
+    /// Only match files modified before this duration-ago or this timestamp.
+    #[arg(long = "changed-before", value_name = "WHEN")]
+    changed_before: Option<String>,
+
+    /// (Unix only) filter by owner, e.g. `--owner user:group`, `--owner !root`.
+    #[arg(long = "owner", value_name = "USER:GROUP")]
+    owner: Option<String>,
+
+    /// When to colorize text output. `auto` colorizes only when stdout is a TTY.
+    #[arg(long, value_enum, default_value_t = ColorMode::Auto)]
+    color: ColorMode,
+
+    /// Text encoding for content search. `auto` (default) sniffs a BOM;
+    /// otherwise any label `encoding_rs` understands, e.g. `UTF-16LE`, `latin1`.
+    #[arg(long, default_value = "auto")]
+    encoding: String,
+
+    /// Transparently decompress `.gz`/`.bz2`/`.xz`/`.zst` files before searching their content.
+    #[arg(short = 'z', long = "search-zip")]
+    search_zip: bool,
+
     /// Set the logging level.
     #[arg(long, value_enum, help = "Set the logging level (error, warn, info, debug, trace)")]
     log_level: Option<LogLevelCli>,
@@ -77,24 +165,15 @@ struct Config {
     log_file: Option<PathBuf>,
 }
 
-#[derive(Debug, Clone)]
-struct Match {
-    path: PathBuf,
-    match_type: MatchType,
-    line_number: Option<usize>,
-    line_content: Option<String>,
-}
-
-#[derive(Debug, Clone)]
-enum MatchType {
-    FileName,
-    DirName,
-    FileContent,
-}
-
 fn main() -> Result<()> {
     let mut config = Config::parse();
 
+    if config.type_list {
+        return filetypes::print_type_list(&config.type_add);
+    }
+
+    config.case_sensitive = case::resolve(config.case, &config.pattern);
+
     // Initialize logger
     let mut log_builder = env_logger::Builder::new();
 
@@ -164,6 +243,13 @@ fn main() -> Result<()> {
     );
 
     let content_matcher = create_content_matcher(&config)?;
+    let file_types = filetypes::build_types(&config.type_, &config.type_not, &config.type_add)?;
+    let metadata_filters = filters::MetadataFilters::build(
+        &config.size,
+        &config.changed_within,
+        &config.changed_before,
+        &config.owner,
+    )?;
     let processed_entry_count = Arc::new(AtomicUsize::new(0));
     let found_items_count_for_progress = Arc::new(AtomicUsize::new(0));
 
@@ -190,6 +276,10 @@ fn main() -> Result<()> {
         debug!("Max search depth set to: {}", max_depth);
         walker.max_depth(Some(max_depth));
     }
+    if let Some(overrides) = globs::build_overrides(&config.path, &config.glob)? {
+        debug!("Applying {} glob override(s)", config.glob.len());
+        walker.overrides(overrides);
+    }
 
     let name_regex_matcher = if config.regex {
         let pattern = if config.case_sensitive {
@@ -216,6 +306,8 @@ fn main() -> Result<()> {
         let config_ref = &config;
         let content_matcher_ref = &content_matcher;
         let name_regex_matcher_ref = &name_regex_matcher;
+        let file_types_ref = &file_types;
+        let metadata_filters_ref = &metadata_filters;
         let progress_bar_ref = &progress_bar;
 
         Box::new(move |result| {
@@ -235,9 +327,20 @@ fn main() -> Result<()> {
                     let file_type = entry.file_type();
                     let is_dir = file_type.map_or(false, |ft| ft.is_dir());
                     let is_file = file_type.map_or(false, |ft| ft.is_file());
+                    let type_ok = !is_file || filetypes::passes_type_filter(file_types_ref, entry.path());
+                    let meta_ok = match metadata_filters_ref {
+                        Some(filters) => match entry.metadata() {
+                            Ok(metadata) => filters.matches(&metadata, is_dir),
+                            Err(e) => {
+                                warn!("Could not read metadata for {}: {}", entry.path().display(), e);
+                                false
+                            }
+                        },
+                        None => true,
+                    };
                     let mut local_matches = Vec::new();
 
-                    if search_dir_names && is_dir {
+                    if search_dir_names && is_dir && meta_ok {
                         let path = entry.path();
                         if let Some(dir_name) = path.file_name().and_then(|n| n.to_str()) {
                             if matches_name(config_ref, dir_name, name_regex_matcher_ref) {
@@ -247,12 +350,13 @@ fn main() -> Result<()> {
                                     match_type: MatchType::DirName,
                                     line_number: None,
                                     line_content: None,
+                                    match_spans: Vec::new(),
                                 });
                             }
                         }
                     }
 
-                    if search_file_names && is_file {
+                    if search_file_names && is_file && type_ok && meta_ok {
                         let path = entry.path();
                         if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
                             if matches_name(config_ref, file_name, name_regex_matcher_ref) {
@@ -262,12 +366,13 @@ fn main() -> Result<()> {
                                     match_type: MatchType::FileName,
                                     line_number: None,
                                     line_content: None,
+                                    match_spans: Vec::new(),
                                 });
                             }
                         }
                     }
 
-                    if search_contents && is_file {
+                    if search_contents && is_file && type_ok && meta_ok {
                         let path = entry.path();
                         debug!("Searching content in file: {}", path.display());
                         match search_file_content(config_ref, content_matcher_ref, path) {
@@ -316,31 +421,56 @@ fn main() -> Result<()> {
         pb.finish_with_message(format!("{}", final_found_count));
     }
 
-    for m in &final_matches_vec {
-        match m.match_type {
-            MatchType::FileName => println!("File: {}", m.path.display()),
-            MatchType::DirName => println!("Directory: {}", m.path.display()),
-            MatchType::FileContent => {
-                println!(
-                    "Content: {}:{}:{}",
-                    m.path.display(),
-                    m.line_number.unwrap_or(0),
-                    m.line_content.as_deref().unwrap_or(""),
-                );
-            }
+    let elapsed = start_time.elapsed();
+
+    // `-x`/`-X` turns finder into a pipeline driver: the exec command's own
+    // output is the result, so the normal match listing and summary are
+    // suppressed rather than printed alongside it.
+    let running_exec = config.exec.is_some() || config.exec_batch.is_some();
+    if !running_exec {
+        let printer = Printer::new(config.format, config.color);
+        for m in &final_matches_vec {
+            printer.print_match(m);
         }
+        printer.print_summary(final_processed_count, final_found_count, elapsed.as_secs_f64());
     }
 
-    let elapsed = start_time.elapsed();
     info!(
         "Search completed in {:.2}s. Processed {} entries, found {} matches.",
         elapsed.as_secs_f64(),
         final_processed_count,
         final_found_count
     );
+
+    if let Some(tokens) = config.exec.clone() {
+        let paths = unique_match_paths(&final_matches_vec);
+        let template = exec::ExecTemplate::new(tokens);
+        let threads = config
+            .threads
+            .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1));
+        let exit_code = exec::run_per_match(&template, &paths, threads);
+        std::process::exit(exit_code);
+    } else if let Some(tokens) = config.exec_batch.clone() {
+        let paths = unique_match_paths(&final_matches_vec);
+        let template = exec::ExecTemplate::new_batch(tokens)?;
+        let exit_code = exec::run_batch(&template, &paths);
+        std::process::exit(exit_code);
+    }
+
     Ok(())
 }
 
+/// Paths of all matches, deduplicated in first-seen order (so a file with
+/// several content-match lines is only handed to `-x`/`-X` once).
+fn unique_match_paths(matches: &[Match]) -> Vec<PathBuf> {
+    let mut seen = std::collections::HashSet::new();
+    matches
+        .iter()
+        .filter(|m| seen.insert(m.path.clone()))
+        .map(|m| m.path.clone())
+        .collect()
+}
+
 fn create_content_matcher(config: &Config) -> Result<RegexMatcher> {
     let pattern_str = if config.regex {
         config.pattern.clone()
@@ -384,19 +514,33 @@ fn search_file_content(config: &Config, matcher: &RegexMatcher, path: &Path) ->
     let mut searcher = SearcherBuilder::new()
         .binary_detection(binary_detection)
         .line_number(true)
+        .encoding(encoding::resolve_encoding(&config.encoding, path, config.search_zip)?)
         .build();
 
-    searcher.search_path(
+    let reader = encoding::open_reader(path, config.search_zip)?;
+
+    searcher.search_reader(
         matcher,
-        path,
+        reader,
         UTF8(|line_number, line| {
             let line_num = line_number.try_into().unwrap_or(usize::MAX);
-            trace!("Content match in {}:{} - {}", path.display(), line_num, line.trim_end());
+            let trimmed = line.trim_end();
+            trace!("Content match in {}:{} - {}", path.display(), line_num, trimmed);
+
+            let mut match_spans = Vec::new();
+            if let Err(e) = matcher.find_iter(trimmed.as_bytes(), |m| {
+                match_spans.push((m.start(), m.end()));
+                true
+            }) {
+                warn!("Failed to compute match spans in {}:{}: {}", path.display(), line_num, e);
+            }
+
             matches.push(Match {
                 path: path.to_path_buf(),
                 match_type: MatchType::FileContent,
                 line_number: Some(line_num),
-                line_content: Some(line.trim_end().to_string()),
+                line_content: Some(trimmed.to_string()),
+                match_spans,
             });
             Ok(true)
         }),
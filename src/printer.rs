@@ -0,0 +1,230 @@
+//! Output formatting for search results: the default text format, colored
+//! via `LS_COLORS` with matched spans highlighted, and a JSON Lines format
+//! modeled on ripgrep's `--json` for `--format json`.
+
+use std::cell::RefCell;
+use std::io::Write;
+use std::path::Path;
+
+use clap::ValueEnum;
+use serde::Serialize;
+use termcolor::{StandardStream, WriteColor};
+
+use crate::color::{self, ColorMode, PathColorizer};
+
+/// Which kind of thing a [`Match`] refers to.
+#[derive(Debug, Clone)]
+pub enum MatchType {
+    FileName,
+    DirName,
+    FileContent,
+}
+
+/// A single search hit, either a name match or a line of file content.
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub path: std::path::PathBuf,
+    pub match_type: MatchType,
+    pub line_number: Option<usize>,
+    pub line_content: Option<String>,
+    /// Byte offsets of each matched span within `line_content`, used to
+    /// highlight just the matched text. Always empty for name matches.
+    pub match_spans: Vec<(usize, usize)>,
+}
+
+/// Selects how results are written to stdout.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// A path that may not be valid UTF-8, serialized the way ripgrep's JSON
+/// printer does: `{"text": ...}` when lossless, `{"bytes": <base64>}`
+/// otherwise so non-UTF-8 filenames survive the round trip.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum PathRepr {
+    Text { text: String },
+    Bytes { bytes: String },
+}
+
+impl PathRepr {
+    fn new(path: &Path) -> Self {
+        match path.to_str() {
+            Some(text) => PathRepr::Text { text: text.to_string() },
+            None => {
+                use base64::Engine;
+                let bytes = base64::engine::general_purpose::STANDARD.encode(path.as_os_str().as_encoded_bytes());
+                PathRepr::Bytes { bytes }
+            }
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+enum JsonRecord {
+    File { path: PathRepr },
+    Dir { path: PathRepr },
+    Content {
+        path: PathRepr,
+        line_number: usize,
+        line: String,
+    },
+    Summary {
+        processed: usize,
+        matched: usize,
+        elapsed_secs: f64,
+    },
+}
+
+/// Writes [`Match`]es to stdout in the configured [`OutputFormat`], coloring
+/// text-mode output by `LS_COLORS` and highlighting matched spans.
+pub struct Printer {
+    format: OutputFormat,
+    stdout: RefCell<StandardStream>,
+    path_colorizer: PathColorizer,
+}
+
+impl Printer {
+    pub fn new(format: OutputFormat, color_mode: ColorMode) -> Self {
+        Printer {
+            format,
+            stdout: RefCell::new(StandardStream::stdout(color_mode.to_termcolor_choice())),
+            path_colorizer: PathColorizer::from_env(),
+        }
+    }
+
+    /// Prints a single match, in text or JSON depending on the configured format.
+    pub fn print_match(&self, m: &Match) {
+        match self.format {
+            OutputFormat::Text => self.print_match_text(m),
+            OutputFormat::Json => print_json_line(&json_record_for(m)),
+        }
+    }
+
+    fn print_match_text(&self, m: &Match) {
+        let mut stdout = self.stdout.borrow_mut();
+        let is_dir = matches!(m.match_type, MatchType::DirName);
+        let label = match m.match_type {
+            MatchType::FileName => "File",
+            MatchType::DirName => "Directory",
+            MatchType::FileContent => "Content",
+        };
+
+        let _ = write!(stdout, "{}: ", label);
+        let _ = stdout.set_color(&self.path_colorizer.spec_for(&m.path, is_dir));
+        let _ = write!(stdout, "{}", m.path.display());
+        let _ = stdout.reset();
+
+        if let MatchType::FileContent = m.match_type {
+            let _ = write!(stdout, ":{}:", m.line_number.unwrap_or(0));
+            write_highlighted_line(&mut *stdout, m);
+        }
+        let _ = writeln!(stdout);
+    }
+
+    /// Prints the trailing summary line. In text mode this is a no-op since
+    /// the human summary is already logged via `info!`.
+    pub fn print_summary(&self, processed: usize, matched: usize, elapsed_secs: f64) {
+        if self.format == OutputFormat::Json {
+            print_json_line(&JsonRecord::Summary { processed, matched, elapsed_secs });
+        }
+    }
+}
+
+/// Writes `line_content`, recoloring just the byte ranges in `match_spans`.
+fn write_highlighted_line(stdout: &mut impl WriteColor, m: &Match) {
+    let line = m.line_content.as_deref().unwrap_or("");
+    let mut cursor = 0;
+    for &(start, end) in &m.match_spans {
+        if start < cursor || end > line.len() || start > end {
+            continue;
+        }
+        let _ = write!(stdout, "{}", &line[cursor..start]);
+        let _ = stdout.set_color(&color::match_highlight_spec());
+        let _ = write!(stdout, "{}", &line[start..end]);
+        let _ = stdout.reset();
+        cursor = end;
+    }
+    let _ = write!(stdout, "{}", &line[cursor..]);
+}
+
+fn json_record_for(m: &Match) -> JsonRecord {
+    match m.match_type {
+        MatchType::FileName => JsonRecord::File { path: PathRepr::new(&m.path) },
+        MatchType::DirName => JsonRecord::Dir { path: PathRepr::new(&m.path) },
+        MatchType::FileContent => JsonRecord::Content {
+            path: PathRepr::new(&m.path),
+            line_number: m.line_number.unwrap_or(0),
+            line: m.line_content.clone().unwrap_or_default(),
+        },
+    }
+}
+
+fn print_json_line(record: &JsonRecord) {
+    match serde_json::to_string(record) {
+        Ok(line) => println!("{}", line),
+        Err(e) => log::error!("Failed to serialize JSON record: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::ffi::OsStrExt;
+    use termcolor::Buffer;
+
+    #[test]
+    fn path_repr_is_text_for_valid_utf8() {
+        let repr = PathRepr::new(Path::new("src/main.rs"));
+        assert_eq!(serde_json::to_string(&repr).unwrap(), r#"{"text":"src/main.rs"}"#);
+    }
+
+    #[test]
+    fn path_repr_falls_back_to_base64_bytes_for_non_utf8() {
+        let non_utf8 = std::ffi::OsStr::from_bytes(&[b'a', 0xFF, b'b']);
+        let repr = PathRepr::new(Path::new(non_utf8));
+        match &repr {
+            PathRepr::Bytes { bytes } => {
+                use base64::Engine;
+                let decoded = base64::engine::general_purpose::STANDARD.decode(bytes).unwrap();
+                assert_eq!(decoded, vec![b'a', 0xFF, b'b']);
+            }
+            PathRepr::Text { .. } => panic!("expected a Bytes fallback for non-UTF-8 path"),
+        }
+    }
+
+    fn highlighted_text(line: &str, spans: Vec<(usize, usize)>) -> String {
+        let m = Match {
+            path: std::path::PathBuf::from("f"),
+            match_type: MatchType::FileContent,
+            line_number: Some(1),
+            line_content: Some(line.to_string()),
+            match_spans: spans,
+        };
+        let mut buf = Buffer::no_color();
+        write_highlighted_line(&mut buf, &m);
+        String::from_utf8(buf.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn highlights_disjoint_spans_in_order() {
+        assert_eq!(highlighted_text("foo bar foo", vec![(0, 3), (8, 11)]), "foo bar foo");
+    }
+
+    #[test]
+    fn skips_spans_that_overlap_an_already_written_span() {
+        // The second span starts before the first one's end, so it must be
+        // skipped rather than rewinding the cursor and corrupting output.
+        assert_eq!(highlighted_text("abcdef", vec![(0, 4), (2, 6)]), "abcdef");
+    }
+
+    #[test]
+    fn skips_spans_out_of_bounds_or_inverted() {
+        assert_eq!(highlighted_text("abc", vec![(1, 10)]), "abc");
+        assert_eq!(highlighted_text("abc", vec![(2, 1)]), "abc");
+    }
+}